@@ -1,6 +1,8 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::collections::HashMap;
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tracing::debug;
 use tracing::error;
 use tracing::info;
@@ -95,6 +97,679 @@ impl ApnsConfig {
     }
 }
 
+/// Delivery priority of an Android message, serialized as the FCM
+/// `AndroidMessagePriority` strings.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub enum AndroidMessagePriority {
+    #[serde(rename = "HIGH")]
+    High,
+    #[serde(rename = "NORMAL")]
+    Normal,
+}
+
+/// Importance of an Android notification, serialized as the FCM
+/// `NotificationPriority` strings.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub enum NotificationPriority {
+    #[serde(rename = "PRIORITY_MIN")]
+    Min,
+    #[serde(rename = "PRIORITY_LOW")]
+    Low,
+    #[serde(rename = "PRIORITY_DEFAULT")]
+    Default,
+    #[serde(rename = "PRIORITY_HIGH")]
+    High,
+    #[serde(rename = "PRIORITY_MAX")]
+    Max,
+}
+
+/// Visibility of an Android notification on a secure lock screen.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub enum NotificationVisibility {
+    #[serde(rename = "PRIVATE")]
+    Private,
+    #[serde(rename = "PUBLIC")]
+    Public,
+    #[serde(rename = "SECRET")]
+    Secret,
+}
+
+/// LED light settings for an Android notification.
+#[derive(Debug, Clone, Serialize)]
+pub struct LightSettings {
+    /// The notification light color, as `#rrggbb` or `#aarrggbb`.
+    pub color: String,
+    /// How long the light stays on, as a `"3.5s"`-style duration string.
+    pub light_on_duration: String,
+    /// How long the light stays off, as a `"3.5s"`-style duration string.
+    pub light_off_duration: String,
+}
+
+/// Android-specific notification options for the `android.notification` key.
+#[derive(Debug, Clone, Serialize)]
+pub struct AndroidNotification {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sound: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub click_action: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub channel_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notification_priority: Option<NotificationPriority>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub visibility: Option<NotificationVisibility>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub light_settings: Option<LightSettings>,
+}
+
+impl AndroidNotification {
+    /// Create a new AndroidNotification with default values
+    pub fn new() -> Self {
+        Self {
+            icon: None,
+            color: None,
+            sound: None,
+            tag: None,
+            click_action: None,
+            channel_id: None,
+            notification_priority: None,
+            visibility: None,
+            light_settings: None,
+        }
+    }
+
+    /// Set the notification icon
+    pub fn with_icon(mut self, icon: String) -> Self {
+        self.icon = Some(icon);
+        self
+    }
+
+    /// Set the notification accent color (`#rrggbb`)
+    pub fn with_color(mut self, color: String) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    /// Set the notification sound
+    pub fn with_sound(mut self, sound: String) -> Self {
+        self.sound = Some(sound);
+        self
+    }
+
+    /// Set the notification tag used to replace existing notifications
+    pub fn with_tag(mut self, tag: String) -> Self {
+        self.tag = Some(tag);
+        self
+    }
+
+    /// Set the action associated with a user tap on the notification
+    pub fn with_click_action(mut self, click_action: String) -> Self {
+        self.click_action = Some(click_action);
+        self
+    }
+
+    /// Set the notification channel id (Android O and above)
+    pub fn with_channel_id(mut self, channel_id: String) -> Self {
+        self.channel_id = Some(channel_id);
+        self
+    }
+
+    /// Set the relative priority of the notification
+    pub fn with_notification_priority(mut self, priority: NotificationPriority) -> Self {
+        self.notification_priority = Some(priority);
+        self
+    }
+
+    /// Set the lock-screen visibility of the notification
+    pub fn with_visibility(mut self, visibility: NotificationVisibility) -> Self {
+        self.visibility = Some(visibility);
+        self
+    }
+
+    /// Set the LED light settings
+    pub fn with_light_settings(mut self, light_settings: LightSettings) -> Self {
+        self.light_settings = Some(light_settings);
+        self
+    }
+}
+
+/// Android-specific options for FCM messages
+#[derive(Debug, Clone, Serialize)]
+pub struct AndroidConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub collapse_key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub priority: Option<AndroidMessagePriority>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ttl: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub restricted_package_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notification: Option<AndroidNotification>,
+}
+
+impl AndroidConfig {
+    /// Create a new AndroidConfig with default values
+    pub fn new() -> Self {
+        Self {
+            collapse_key: None,
+            priority: None,
+            ttl: None,
+            restricted_package_name: None,
+            data: None,
+            notification: None,
+        }
+    }
+
+    /// Set the collapse key used to group messages that can be replaced
+    pub fn with_collapse_key(mut self, collapse_key: String) -> Self {
+        self.collapse_key = Some(collapse_key);
+        self
+    }
+
+    /// Set the message delivery priority
+    pub fn with_priority(mut self, priority: AndroidMessagePriority) -> Self {
+        self.priority = Some(priority);
+        self
+    }
+
+    /// Set how long the message should be kept in FCM storage.
+    ///
+    /// The duration is serialized as the FCM-expected `"3.5s"`-style string.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        let secs = ttl.as_secs();
+        let nanos = ttl.subsec_nanos();
+        self.ttl = Some(if nanos == 0 {
+            format!("{secs}s")
+        } else {
+            format!("{secs}.{nanos:09}s")
+        });
+        self
+    }
+
+    /// Set the package name of the application where the token must match
+    pub fn with_restricted_package_name(mut self, restricted_package_name: String) -> Self {
+        self.restricted_package_name = Some(restricted_package_name);
+        self
+    }
+
+    /// Set the arbitrary key/value payload for this message
+    pub fn with_data(mut self, data: Value) -> Self {
+        self.data = Some(data);
+        self
+    }
+
+    /// Set the Android-specific notification options
+    pub fn with_notification(mut self, notification: AndroidNotification) -> Self {
+        self.notification = Some(notification);
+        self
+    }
+}
+
+/// FCM options for WebPush
+#[derive(Debug, Clone, Serialize)]
+pub struct WebpushFcmOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub link: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub analytics_label: Option<String>,
+}
+
+impl WebpushFcmOptions {
+    /// Create a new WebpushFcmOptions with default values
+    pub fn new() -> Self {
+        Self {
+            link: None,
+            analytics_label: None,
+        }
+    }
+
+    /// Set the link to open when the user clicks the notification
+    pub fn with_link(mut self, link: String) -> Self {
+        self.link = Some(link);
+        self
+    }
+
+    /// Set the analytics label associated with the message
+    pub fn with_analytics_label(mut self, analytics_label: String) -> Self {
+        self.analytics_label = Some(analytics_label);
+        self
+    }
+}
+
+/// WebPush-specific options for FCM messages
+#[derive(Debug, Clone, Serialize)]
+pub struct WebpushConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub headers: Option<HashMap<String, String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notification: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fcm_options: Option<WebpushFcmOptions>,
+}
+
+impl WebpushConfig {
+    /// Create a new WebpushConfig with default values
+    pub fn new() -> Self {
+        Self {
+            headers: None,
+            data: None,
+            notification: None,
+            fcm_options: None,
+        }
+    }
+
+    /// Set the HTTP headers (like `TTL`, `Urgency`) forwarded to the push
+    /// service
+    pub fn with_headers(mut self, headers: HashMap<String, String>) -> Self {
+        self.headers = Some(headers);
+        self
+    }
+
+    /// Set the arbitrary key/value payload for this message
+    pub fn with_data(mut self, data: Value) -> Self {
+        self.data = Some(data);
+        self
+    }
+
+    /// Set the free-form Web Notification options (title, body, icon,
+    /// actions, badge, ...)
+    pub fn with_notification(mut self, notification: Value) -> Self {
+        self.notification = Some(notification);
+        self
+    }
+
+    /// Set the FCM options for WebPush
+    pub fn with_fcm_options(mut self, fcm_options: WebpushFcmOptions) -> Self {
+        self.fcm_options = Some(fcm_options);
+        self
+    }
+}
+
+/// The recipient of an FCM message.
+///
+/// Every message must be addressed to exactly one of a registration token, a
+/// topic name, or a condition expression, which map to the mutually-exclusive
+/// `token`/`topic`/`condition` keys of the FCM v1 message.
+#[derive(Debug, Clone)]
+pub enum Target {
+    /// A single device registration token.
+    Token(String),
+    /// A topic name, e.g. `"news"` (the `/topics/` prefix is optional).
+    Topic(String),
+    /// A condition expression, e.g.
+    /// `"'stock-GOOG' in topics && 'industry-tech' in topics"`.
+    Condition(String),
+}
+
+/// The `errorCode` of a structured FCM v1 error response.
+///
+/// These map to the `errorCode` values in the `FcmError` detail of a failed
+/// `messages:send` call. Unknown or unmapped codes are kept verbatim in
+/// [`FcmErrorCode::Other`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FcmErrorCode {
+    /// The registration token is no longer valid; the caller should delete it.
+    Unregistered,
+    /// The request was malformed, often an invalid token; delete it.
+    InvalidArgument,
+    /// The sending limit was exceeded; retry with backoff.
+    QuotaExceeded,
+    /// The server is temporarily unavailable; retry with backoff.
+    Unavailable,
+    /// The token does not match the sender id used to send the message.
+    SenderIdMismatch,
+    /// Authentication with APNs or the web push service failed.
+    ThirdPartyAuthError,
+    /// An `errorCode` that is not explicitly handled.
+    Other(String),
+}
+
+impl FcmErrorCode {
+    fn from_code(code: &str) -> Self {
+        match code {
+            "UNREGISTERED" => FcmErrorCode::Unregistered,
+            "INVALID_ARGUMENT" => FcmErrorCode::InvalidArgument,
+            "QUOTA_EXCEEDED" => FcmErrorCode::QuotaExceeded,
+            "UNAVAILABLE" => FcmErrorCode::Unavailable,
+            "SENDER_ID_MISMATCH" => FcmErrorCode::SenderIdMismatch,
+            "THIRD_PARTY_AUTH_ERROR" => FcmErrorCode::ThirdPartyAuthError,
+            other => FcmErrorCode::Other(other.to_string()),
+        }
+    }
+
+    /// Whether the token that triggered this error should be deleted by the
+    /// caller (i.e. `UNREGISTERED` or `INVALID_ARGUMENT`).
+    pub fn is_token_invalid(&self) -> bool {
+        matches!(self, FcmErrorCode::Unregistered | FcmErrorCode::InvalidArgument)
+    }
+
+    /// Whether the request can be retried later (i.e. `QUOTA_EXCEEDED` or
+    /// `UNAVAILABLE`).
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, FcmErrorCode::QuotaExceeded | FcmErrorCode::Unavailable)
+    }
+}
+
+/// A structured error body returned by the FCM v1 `messages:send` endpoint.
+#[derive(Debug, Clone)]
+pub struct FcmServerError {
+    /// The HTTP status code of the response.
+    pub http_status: u16,
+    /// The `status` string, e.g. `"NOT_FOUND"`.
+    pub status: String,
+    /// The human-readable `message`.
+    pub message: String,
+    /// The classified `errorCode` from the error details.
+    pub error_code: FcmErrorCode,
+}
+
+impl FcmServerError {
+    /// Whether the offending token should be deleted by the caller.
+    pub fn is_token_invalid(&self) -> bool {
+        self.error_code.is_token_invalid()
+    }
+
+    /// Whether the request can be retried later.
+    pub fn is_retryable(&self) -> bool {
+        self.error_code.is_retryable()
+    }
+
+    /// Attempt to parse a structured FCM error from the response body, falling
+    /// back to `None` when the body does not match the expected shape.
+    fn from_body(http_status: u16, body: &str) -> Option<Self> {
+        let parsed: FcmErrorResponse = serde_json::from_str(body).ok()?;
+        let error = parsed.error;
+        let error_code = error
+            .details
+            .iter()
+            .find_map(|detail| detail.error_code.as_deref())
+            .map(FcmErrorCode::from_code)?;
+        Some(FcmServerError {
+            http_status,
+            status: error.status,
+            message: error.message,
+            error_code,
+        })
+    }
+}
+
+impl FcmError {
+    /// Whether this error indicates the target token is permanently invalid
+    /// and should be removed from the caller's token store.
+    pub fn is_token_invalid(&self) -> bool {
+        matches!(self, FcmError::Fcm(error) if error.is_token_invalid())
+    }
+
+    /// Whether this error is transient and the message may be retried later.
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, FcmError::Fcm(error) if error.is_retryable())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct FcmErrorResponse {
+    error: FcmErrorBody,
+}
+
+#[derive(Debug, Deserialize)]
+struct FcmErrorBody {
+    #[serde(default)]
+    status: String,
+    #[serde(default)]
+    message: String,
+    #[serde(default)]
+    details: Vec<FcmErrorDetail>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FcmErrorDetail {
+    #[serde(rename = "errorCode")]
+    error_code: Option<String>,
+}
+
+/// Controls how transient `429`/`503` responses are retried.
+///
+/// When no `Retry-After` header is present, the delay before retry `n`
+/// (zero-based) is `base_delay * 2^n`, capped at `max_delay`, with full jitter
+/// applied to avoid a thundering herd.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// The maximum number of attempts, including the first one.
+    pub max_attempts: u32,
+    /// The initial backoff delay, doubled on each subsequent attempt.
+    pub base_delay: Duration,
+    /// The upper bound on a single backoff delay.
+    pub max_delay: Duration,
+}
+
+impl RetryPolicy {
+    /// Create a new RetryPolicy with sensible defaults (3 attempts, 1s base,
+    /// 60s cap).
+    pub fn new() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(60),
+        }
+    }
+
+    /// Set the maximum number of attempts, including the first.
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    /// Set the initial backoff delay.
+    pub fn with_base_delay(mut self, base_delay: Duration) -> Self {
+        self.base_delay = base_delay;
+        self
+    }
+
+    /// Set the upper bound on a single backoff delay.
+    pub fn with_max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// The jittered backoff delay for a zero-based retry index.
+    fn backoff_delay(&self, retry_index: u32) -> Duration {
+        let exponential = self
+            .base_delay
+            .saturating_mul(1u32 << retry_index.min(31));
+        let capped = exponential.min(self.max_delay);
+        // Full jitter in `[0, capped)`, seeded from the current time to avoid
+        // pulling in a random-number-generator dependency.
+        let fraction = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.subsec_nanos() as f64 / 1_000_000_000.0)
+            .unwrap_or(0.0);
+        capped.mul_f64(fraction)
+    }
+}
+
+/// Parse a `Retry-After` header value, supporting both the integer-seconds and
+/// HTTP-date (IMF-fixdate) forms.
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+
+    // Delta-seconds form, e.g. `Retry-After: 120`.
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    // HTTP-date form, e.g. `Retry-After: Wed, 21 Oct 2015 07:28:00 GMT`.
+    let target = parse_imf_fixdate(value)?;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .ok()?
+        .as_secs();
+    Some(Duration::from_secs(target.saturating_sub(now)))
+}
+
+/// Parse an IMF-fixdate (`"Wed, 21 Oct 2015 07:28:00 GMT"`) into a Unix
+/// timestamp in seconds.
+fn parse_imf_fixdate(value: &str) -> Option<u64> {
+    // "Wed, 21 Oct 2015 07:28:00 GMT"
+    let rest = value.split_once(", ")?.1;
+    let mut parts = rest.split(' ');
+    let day: u64 = parts.next()?.parse().ok()?;
+    let month = match parts.next()? {
+        "Jan" => 1,
+        "Feb" => 2,
+        "Mar" => 3,
+        "Apr" => 4,
+        "May" => 5,
+        "Jun" => 6,
+        "Jul" => 7,
+        "Aug" => 8,
+        "Sep" => 9,
+        "Oct" => 10,
+        "Nov" => 11,
+        "Dec" => 12,
+        _ => return None,
+    };
+    let year: u64 = parts.next()?.parse().ok()?;
+    let mut time = parts.next()?.split(':');
+    let hour: u64 = time.next()?.parse().ok()?;
+    let minute: u64 = time.next()?.parse().ok()?;
+    let second: u64 = time.next()?.parse().ok()?;
+
+    // Days from the Unix epoch to the start of `year`.
+    let mut days: u64 = 0;
+    for y in 1970..year {
+        days += if is_leap_year(y) { 366 } else { 365 };
+    }
+    let month_days = [31, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+    for (m, length) in month_days.iter().enumerate() {
+        if (m as u64 + 1) >= month {
+            break;
+        }
+        days += length;
+        if m == 1 && is_leap_year(year) {
+            days += 1;
+        }
+    }
+    days += day - 1;
+
+    Some(days * 86_400 + hour * 3_600 + minute * 60 + second)
+}
+
+fn is_leap_year(year: u64) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+/// A reusable FCM client that owns a single `reqwest::Client`.
+///
+/// Constructing one client and reusing it across sends keeps the HTTP/2
+/// connection and TLS session to `fcm.googleapis.com` alive, avoiding the
+/// per-call cost of `reqwest::Client::new()`. It also bundles the
+/// `SharedTokenManager` and `project_id` so individual sends only need the
+/// message itself.
+pub struct FcmClient {
+    client: reqwest::Client,
+    token_manager: SharedTokenManager,
+    project_id: String,
+    retry_policy: Option<RetryPolicy>,
+}
+
+impl FcmClient {
+    /// Create a new `FcmClient` whose HTTP requests use a 30-second timeout.
+    pub fn new(
+        token_manager: SharedTokenManager,
+        project_id: impl Into<String>,
+    ) -> Result<Self, FcmError> {
+        Self::with_timeout(token_manager, project_id, Duration::from_secs(30))
+    }
+
+    /// Create a new `FcmClient` whose HTTP requests use the given timeout.
+    pub fn with_timeout(
+        token_manager: SharedTokenManager,
+        project_id: impl Into<String>,
+        timeout: Duration,
+    ) -> Result<Self, FcmError> {
+        let client = reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .map_err(NetworkError::SendRequestError)
+            .map_fcm_err()?;
+        Ok(Self {
+            client,
+            token_manager,
+            project_id: project_id.into(),
+            retry_policy: None,
+        })
+    }
+
+    /// Enable automatic retries of transient `429`/`503` responses using the
+    /// given [`RetryPolicy`].
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(retry_policy);
+        self
+    }
+
+    /// Send an FCM message to a device token, reusing the pooled connection.
+    pub async fn send<T: Serialize>(
+        &self,
+        device_token: &str,
+        notification: Option<FcmNotification>,
+        data_payload: Option<T>,
+        apns_config: Option<ApnsConfig>,
+        android_config: Option<AndroidConfig>,
+        webpush_config: Option<WebpushConfig>,
+    ) -> Result<(), FcmError> {
+        self.send_to_target(
+            Target::Token(device_token.to_string()),
+            notification,
+            data_payload,
+            apns_config,
+            android_config,
+            webpush_config,
+        )
+        .await
+    }
+
+    /// Send an FCM message to the given [`Target`], reusing the pooled
+    /// connection.
+    pub async fn send_to_target<T: Serialize>(
+        &self,
+        target: Target,
+        notification: Option<FcmNotification>,
+        data_payload: Option<T>,
+        apns_config: Option<ApnsConfig>,
+        android_config: Option<AndroidConfig>,
+        webpush_config: Option<WebpushConfig>,
+    ) -> Result<(), FcmError> {
+        let url = format!(
+            "https://fcm.googleapis.com/v1/projects/{}/messages:send",
+            self.project_id
+        );
+        send_with_client(
+            &self.client,
+            target,
+            notification,
+            data_payload,
+            apns_config,
+            android_config,
+            webpush_config,
+            &self.token_manager,
+            self.retry_policy.as_ref(),
+            &url,
+        )
+        .await
+    }
+}
+
 /// Sends a Firebase Cloud Messaging (FCM) message.
 ///
 /// This function sends an FCM message to the device with the provided device
@@ -109,6 +784,10 @@ impl ApnsConfig {
 /// * `data_payload` - Optional data represented as a Map. This can be any type
 ///   that implements the `Serialize` trait.
 /// * `apns_config` - Optional APNS-specific configuration for iOS devices.
+/// * `android_config` - Optional Android-specific configuration for Android
+///   devices.
+/// * `webpush_config` - Optional WebPush-specific configuration for browser
+///   delivery.
 /// * `token_manager` - A `SharedTokenManager` to handle OAuth tokens.
 /// * `project_id` - The ID of the Firebase project, where the device token is
 ///   registered.
@@ -136,32 +815,69 @@ impl ApnsConfig {
 /// let apns_config = Some(ApnsConfig::silent_push());
 /// let token_manager = create_shared_token_manager(File::open("path_to_google_credentials.json").expect("Failed to open file")).expect("Failed to create SharedTokenManager");
 /// let project_id = "project_id";
-/// send_fcm_message(device_token, Some(notification), Some(data), apns_config, &token_manager, project_id)
+/// send_fcm_message(device_token, Some(notification), Some(data), apns_config, None, None, &token_manager, project_id)
 ///     .await
 ///     .expect("Error while sending FCM message");
 ///
 /// # });
 /// ```
+#[allow(clippy::too_many_arguments)]
 #[instrument(
     level = "info",
-    skip(data_payload, notification, apns_config, token_manager)
+    skip(data_payload, notification, apns_config, android_config, webpush_config, token_manager)
 )]
 pub async fn send_fcm_message<T: Serialize>(
     device_token: &str,
     notification: Option<FcmNotification>,
     data_payload: Option<T>,
     apns_config: Option<ApnsConfig>,
+    android_config: Option<AndroidConfig>,
+    webpush_config: Option<WebpushConfig>,
     token_manager: &SharedTokenManager,
     project_id: &str,
 ) -> Result<(), FcmError> {
-    info!("Sending FCM message to device: {}", device_token);
+    send_fcm_message_to_target(
+        Target::Token(device_token.to_string()),
+        notification,
+        data_payload,
+        apns_config,
+        android_config,
+        webpush_config,
+        token_manager,
+        project_id,
+    )
+    .await
+}
+
+/// Sends a Firebase Cloud Messaging (FCM) message to the given [`Target`].
+///
+/// This behaves like [`send_fcm_message`] but lets you address the message to a
+/// registration token, a topic, or a condition expression.
+#[allow(clippy::too_many_arguments)]
+#[instrument(
+    level = "info",
+    skip(data_payload, notification, apns_config, android_config, webpush_config, token_manager)
+)]
+pub async fn send_fcm_message_to_target<T: Serialize>(
+    target: Target,
+    notification: Option<FcmNotification>,
+    data_payload: Option<T>,
+    apns_config: Option<ApnsConfig>,
+    android_config: Option<AndroidConfig>,
+    webpush_config: Option<WebpushConfig>,
+    token_manager: &SharedTokenManager,
+    project_id: &str,
+) -> Result<(), FcmError> {
+    info!("Sending FCM message to target: {:?}", target);
     let url = format!("https://fcm.googleapis.com/v1/projects/{project_id}/messages:send");
 
-    send_fcm_message_with_url(
-        device_token,
+    send_fcm_message_to_target_with_url(
+        target,
         notification,
         data_payload,
         apns_config,
+        android_config,
+        webpush_config,
         token_manager,
         &url,
     )
@@ -175,43 +891,155 @@ pub async fn send_fcm_message<T: Serialize>(
 ///
 /// Normally, you would use `send_fcm` instead of this function. This is only
 /// useful for testing, such as for mocking the FCM URL.
+#[allow(clippy::too_many_arguments)]
 #[instrument(
     level = "debug",
-    skip(data_payload, notification, apns_config, token_manager)
+    skip(data_payload, notification, apns_config, android_config, webpush_config, token_manager)
 )]
 pub async fn send_fcm_message_with_url<T: Serialize>(
     device_token: &str,
     notification: Option<FcmNotification>,
     data_payload: Option<T>,
     apns_config: Option<ApnsConfig>,
+    android_config: Option<AndroidConfig>,
+    webpush_config: Option<WebpushConfig>,
     token_manager: &SharedTokenManager,
     fcm_url: &str,
 ) -> Result<(), FcmError> {
-    let access_token = {
-        let mut token_manager_guard = token_manager.lock().await;
-        token_manager_guard.get_token().await?
-    };
+    send_fcm_message_to_target_with_url(
+        Target::Token(device_token.to_string()),
+        notification,
+        data_payload,
+        apns_config,
+        android_config,
+        webpush_config,
+        token_manager,
+        fcm_url,
+    )
+    .await
+}
+
+/// Sends a Firebase Cloud Messaging (FCM) message to a [`Target`] at a specific
+/// URL.
+///
+/// This behaves like [`send_fcm_message_to_target`] but allows specifying a
+/// custom FCM URL, which is mainly useful for testing.
+#[allow(clippy::too_many_arguments)]
+#[instrument(
+    level = "debug",
+    skip(data_payload, notification, apns_config, android_config, webpush_config, token_manager)
+)]
+pub async fn send_fcm_message_to_target_with_url<T: Serialize>(
+    target: Target,
+    notification: Option<FcmNotification>,
+    data_payload: Option<T>,
+    apns_config: Option<ApnsConfig>,
+    android_config: Option<AndroidConfig>,
+    webpush_config: Option<WebpushConfig>,
+    token_manager: &SharedTokenManager,
+    fcm_url: &str,
+) -> Result<(), FcmError> {
+    send_with_client(
+        shared_client(),
+        target,
+        notification,
+        data_payload,
+        apns_config,
+        android_config,
+        webpush_config,
+        token_manager,
+        None,
+        fcm_url,
+    )
+    .await
+}
 
-    let client = reqwest::Client::new();
+/// The shared `reqwest::Client` used by the free send functions.
+///
+/// It is initialized once and reused so that the HTTP/2 connection and TLS
+/// session to `fcm.googleapis.com` are kept alive across calls.
+fn shared_client() -> &'static reqwest::Client {
+    static SHARED_CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    SHARED_CLIENT.get_or_init(reqwest::Client::new)
+}
 
-    let payload = create_payload(device_token, notification, data_payload, apns_config)?;
+/// Sends an already-addressed FCM message using the provided `reqwest::Client`,
+/// so connection pooling is controlled by the caller.
+///
+/// When a `retry_policy` is supplied, `429 TOO_MANY_REQUESTS` and
+/// `503 SERVICE_UNAVAILABLE` responses are retried: the `Retry-After` header is
+/// honored if present, otherwise an exponential backoff with jitter is used.
+/// Because `messages:send` is idempotent for a given payload, retrying the POST
+/// is safe. All other non-success statuses fail immediately.
+#[allow(clippy::too_many_arguments)]
+async fn send_with_client<T: Serialize>(
+    client: &reqwest::Client,
+    target: Target,
+    notification: Option<FcmNotification>,
+    data_payload: Option<T>,
+    apns_config: Option<ApnsConfig>,
+    android_config: Option<AndroidConfig>,
+    webpush_config: Option<WebpushConfig>,
+    token_manager: &SharedTokenManager,
+    retry_policy: Option<&RetryPolicy>,
+    fcm_url: &str,
+) -> Result<(), FcmError> {
+    // Build the payload once so it can be re-sent verbatim on each retry.
+    let payload = create_payload(
+        &target,
+        notification,
+        data_payload,
+        apns_config,
+        android_config,
+        webpush_config,
+    )?;
 
-    debug!("Requesting access token");
+    let max_attempts = retry_policy.map(|policy| policy.max_attempts).unwrap_or(1);
+    let mut attempt = 0;
 
-    let res = client
-        .post(fcm_url)
-        .bearer_auth(access_token)
-        .json(&payload)
-        .send()
-        .await
-        .map_err(NetworkError::SendRequestError)
-        .map_fcm_err()?;
+    loop {
+        let access_token = {
+            let mut token_manager_guard = token_manager.lock().await;
+            token_manager_guard.get_token().await?
+        };
+
+        debug!("Requesting access token");
+
+        let res = client
+            .post(fcm_url)
+            .bearer_auth(access_token)
+            .json(&payload)
+            .send()
+            .await
+            .map_err(NetworkError::SendRequestError)
+            .map_fcm_err()?;
+
+        if res.status().is_success() {
+            debug!("FCM message sent successfully");
+            return Ok(());
+        }
 
-    if res.status().is_success() {
-        debug!("FCM message sent successfully");
-        Ok(())
-    } else {
         let status = res.status().as_u16();
+
+        // Retry transient 429/503 responses while attempts remain.
+        attempt += 1;
+        if let Some(policy) = retry_policy {
+            if (status == 429 || status == 503) && attempt < max_attempts {
+                let retry_after = res
+                    .headers()
+                    .get(reqwest::header::RETRY_AFTER)
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(parse_retry_after);
+                let delay = retry_after.unwrap_or_else(|| policy.backoff_delay(attempt - 1));
+                info!(
+                    "FCM returned status {}, retrying in {:?} (attempt {}/{})",
+                    status, delay, attempt, max_attempts
+                );
+                tokio::time::sleep(delay).await;
+                continue;
+            }
+        }
+
         let text = res
             .text()
             .await
@@ -221,20 +1049,29 @@ pub async fn send_fcm_message_with_url<T: Serialize>(
             "FCM message send successfully, but server returned an error. Status: {}, Response: {}",
             status, text
         );
-        Err(NetworkError::ServerError(status, Some(text))).map_fcm_err()
+        // Prefer the structured FCM error body so callers can distinguish a
+        // dead token from a transient outage, falling back to the raw body.
+        return match FcmServerError::from_body(status, &text) {
+            Some(server_error) => Err(FcmError::Fcm(server_error)),
+            None => Err(NetworkError::ServerError(status, Some(text))).map_fcm_err(),
+        };
     }
 }
 
 fn create_payload<T: Serialize>(
-    device_token: &str,
+    target: &Target,
     notification: Option<FcmNotification>,
     data_payload: Option<T>,
     apns_config: Option<ApnsConfig>,
+    android_config: Option<AndroidConfig>,
+    webpush_config: Option<WebpushConfig>,
 ) -> Result<serde_json::Value, FcmError> {
-    // Start with base message
-    let mut message = json!({
-        "token": device_token
-    });
+    // Address the message to exactly one of token/topic/condition.
+    let mut message = match target {
+        Target::Token(token) => json!({ "token": token }),
+        Target::Topic(topic) => json!({ "topic": topic.strip_prefix("/topics/").unwrap_or(topic) }),
+        Target::Condition(condition) => json!({ "condition": condition }),
+    };
 
     // Add notification if provided
     if let Some(notification) = notification {
@@ -256,10 +1093,25 @@ fn create_payload<T: Serialize>(
             serde_json::to_value(apns_config).map_err(FcmError::SerializationError)?;
     }
 
-    // Validate that we have at least one of: notification, data, or apns
+    // Add Android config if provided
+    if let Some(android_config) = android_config {
+        message["android"] =
+            serde_json::to_value(android_config).map_err(FcmError::SerializationError)?;
+    }
+
+    // Add WebPush config if provided
+    if let Some(webpush_config) = webpush_config {
+        message["webpush"] =
+            serde_json::to_value(webpush_config).map_err(FcmError::SerializationError)?;
+    }
+
+    // Validate that we have at least one of: notification, data, apns, android,
+    // or webpush
     if message.get("notification").is_none()
         && message.get("data").is_none()
         && message.get("apns").is_none()
+        && message.get("android").is_none()
+        && message.get("webpush").is_none()
     {
         return Err(FcmError::FcmInvalidPayloadError);
     }
@@ -284,7 +1136,7 @@ fn create_payload<T: Serialize>(
 //             "key": "value"
 //         }));
 
-//         let payload = create_payload(device_token, notification, data_payload, None).unwrap();
+//         let payload = create_payload(&Target::Token(device_token.to_string()), notification, data_payload, None, None, None).unwrap();
 //         assert_eq!(payload["message"]["token"], device_token);
 //         assert_eq!(payload["message"]["notification"]["title"], "Test Title");
 //         assert_eq!(payload["message"]["notification"]["body"], "Test Body");
@@ -300,7 +1152,7 @@ fn create_payload<T: Serialize>(
 //         });
 //         let data_payload: Option<serde_json::Value> = None;
 
-//         let payload = create_payload(device_token, notification, data_payload, None).unwrap();
+//         let payload = create_payload(&Target::Token(device_token.to_string()), notification, data_payload, None, None, None).unwrap();
 //         assert_eq!(payload["message"]["token"], device_token);
 //         assert_eq!(payload["message"]["notification"]["title"], "Test Title");
 //         assert_eq!(payload["message"]["notification"]["body"], "Test Body");
@@ -315,7 +1167,7 @@ fn create_payload<T: Serialize>(
 //             "key": "value"
 //         }));
 
-//         let payload = create_payload(device_token, notification, data_payload, None).unwrap();
+//         let payload = create_payload(&Target::Token(device_token.to_string()), notification, data_payload, None, None, None).unwrap();
 //         assert_eq!(payload["message"]["token"], device_token);
 //         assert!(payload["message"]["notification"].is_null());
 //         assert_eq!(payload["message"]["data"]["key"], "value");
@@ -330,7 +1182,7 @@ fn create_payload<T: Serialize>(
 //         }));
 //         let apns_config = Some(ApnsConfig::silent_push());
 
-//         let payload = create_payload(device_token, notification, data_payload, apns_config).unwrap();
+//         let payload = create_payload(&Target::Token(device_token.to_string()), notification, data_payload, apns_config, None, None).unwrap();
 //         assert_eq!(payload["message"]["token"], device_token);
 //         assert_eq!(payload["message"]["apns"]["payload"]["aps"]["content-available"], 1);
 //         assert_eq!(payload["message"]["data"]["key"], "value");
@@ -351,7 +1203,7 @@ fn create_payload<T: Serialize>(
 //             key2: "value2".to_string(),
 //         };
 
-//         let payload = create_payload(device_token, notification, Some(data_payload), None).unwrap();
+//         let payload = create_payload(&Target::Token(device_token.to_string()), notification, Some(data_payload), None, None, None).unwrap();
 //         assert_eq!(payload["message"]["token"], device_token);
 //         assert!(payload["message"]["notification"].is_null());
 //         assert_eq!(payload["message"]["data"]["key1"], "value1");
@@ -364,7 +1216,7 @@ fn create_payload<T: Serialize>(
 //         let notification: Option<FcmNotification> = None;
 //         let data_payload: Option<serde_json::Value> = None;
 
-//         let payload = create_payload(device_token, notification, data_payload, None);
+//         let payload = create_payload(&Target::Token(device_token.to_string()), notification, data_payload, None, None, None);
 //         assert!(payload.is_err());
 //     }
 
@@ -375,7 +1227,7 @@ fn create_payload<T: Serialize>(
 //         let data_payload: Option<serde_json::Value> = None;
 //         let apns_config = Some(ApnsConfig::silent_push());
 
-//         let payload = create_payload(device_token, notification, data_payload, apns_config).unwrap();
+//         let payload = create_payload(&Target::Token(device_token.to_string()), notification, data_payload, apns_config, None, None).unwrap();
 //         assert_eq!(payload["message"]["token"], device_token);
 //         assert_eq!(payload["message"]["apns"]["payload"]["aps"]["content-available"], 1);
 //         assert!(payload["message"]["notification"].is_null());